@@ -0,0 +1,123 @@
+use std::sync::Mutex;
+
+/// Sizes each DB transaction by a wall-clock cost budget rather than a fixed
+/// update count. We keep an EWMA of how long recent `issue()` calls took per
+/// update and steer the target batch size so each transaction aims for
+/// `target_issue_ms`, backing off when the transaction-permit semaphore is
+/// saturated and growing (up to `max_size`) when permits are free and the
+/// channel is backlogged.
+pub struct AdaptiveBatcher {
+    inner: Mutex<Inner>,
+    min_size: usize,
+    max_size: usize,
+    target_issue_ms: f64,
+    alpha: f64,
+}
+
+struct Inner {
+    target: usize,
+    /// EWMA of milliseconds spent per update in `issue()`. `None` until we have
+    /// our first observation.
+    ms_per_update: Option<f64>,
+}
+
+impl AdaptiveBatcher {
+    pub fn new(initial_size: usize, max_size: usize, target_issue_ms: u64) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                target: initial_size.clamp(1, max_size),
+                ms_per_update: None,
+            }),
+            min_size: 1,
+            max_size,
+            target_issue_ms: target_issue_ms as f64,
+            alpha: 0.2,
+        }
+    }
+
+    /// The batch size the coordinator should currently aim to fill.
+    pub fn target(&self) -> usize {
+        self.inner.lock().unwrap().target
+    }
+
+    /// Fold an observed `issue()` call into the cost estimate.
+    pub fn observe(&self, issue_ms: f64, update_count: usize) {
+        if update_count == 0 {
+            return;
+        }
+        let sample = issue_ms / update_count as f64;
+        let mut inner = self.inner.lock().unwrap();
+        inner.ms_per_update = Some(match inner.ms_per_update {
+            Some(prev) => prev * (1.0 - self.alpha) + sample * self.alpha,
+            None => sample,
+        });
+    }
+
+    /// Recompute the target batch size from the current cost estimate and the
+    /// live backpressure signals. `saturated` means all transaction permits are
+    /// in use; `backlogged` means the channel still has queued updates.
+    pub fn adjust(&self, saturated: bool, backlogged: bool) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let budget_target = match inner.ms_per_update {
+            Some(ms) if ms > 0.0 => (self.target_issue_ms / ms).round() as usize,
+            _ => inner.target,
+        };
+
+        inner.target = if saturated {
+            // DB is the bottleneck; shrink so in-flight transactions stay short.
+            (inner.target / 2).max(self.min_size)
+        } else if backlogged {
+            // Permits to spare and work waiting; grow toward the cost budget.
+            inner.target.max(1).saturating_add(inner.target / 2).min(budget_target.min(self.max_size))
+        } else {
+            budget_target
+        }
+        .clamp(self.min_size, self.max_size);
+
+        inner.target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saturation_shrinks_target() {
+        let batcher = AdaptiveBatcher::new(100, 1000, 50);
+        assert_eq!(batcher.adjust(true, false), 50);
+        assert_eq!(batcher.adjust(true, false), 25);
+    }
+
+    #[test]
+    fn shrink_never_drops_below_one() {
+        let batcher = AdaptiveBatcher::new(1, 1000, 50);
+        assert_eq!(batcher.adjust(true, false), 1);
+    }
+
+    #[test]
+    fn idle_steers_toward_the_cost_budget() {
+        let batcher = AdaptiveBatcher::new(100, 1000, 50);
+        // 1ms per update observed; a 50ms budget targets ~50 updates.
+        batcher.observe(100.0, 100);
+        assert_eq!(batcher.adjust(false, false), 50);
+    }
+
+    #[test]
+    fn backlog_grows_toward_budget_when_permits_are_free() {
+        let batcher = AdaptiveBatcher::new(10, 1000, 50);
+        // Very cheap updates push the budget target well above the current size.
+        batcher.observe(10.0, 1000);
+        // Grows by 50% (10 -> 15) without overshooting the budget ceiling.
+        assert_eq!(batcher.adjust(false, true), 15);
+    }
+
+    #[test]
+    fn target_is_clamped_to_max() {
+        let batcher = AdaptiveBatcher::new(10, 20, 50);
+        batcher.observe(1.0, 1000);
+        for _ in 0..10 {
+            assert!(batcher.adjust(false, true) <= 20);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::str::FromStr;
+
+use envconfig::Envconfig;
+use rdkafka::ClientConfig;
+
+#[derive(Envconfig, Clone)]
+pub struct Config {
+    #[envconfig(from = "BIND_HOST", default = "::")]
+    pub host: String,
+
+    #[envconfig(from = "BIND_PORT", default = "3301")]
+    pub port: u16,
+
+    #[envconfig(nested = true)]
+    pub kafka: KafkaConfig,
+
+    // How many producer loops to run concurrently off the shared consumer.
+    #[envconfig(default = "1")]
+    pub worker_loop_count: usize,
+
+    // Size of the mpsc channel between producers and the coordinator, expressed
+    // as a multiple of the (initial) update batch size per worker.
+    #[envconfig(default = "2")]
+    pub channel_slots_per_worker: usize,
+
+    // Initial/fallback batch size; the adaptive batcher (chunk0-3) grows and
+    // shrinks the live target between 1 and `max_update_batch_size`.
+    #[envconfig(default = "10000")]
+    pub update_batch_size: usize,
+
+    #[envconfig(default = "50000")]
+    pub max_update_batch_size: usize,
+
+    // Wall-clock target (ms) the adaptive batcher steers each transaction toward.
+    #[envconfig(default = "50")]
+    pub target_issue_ms: u64,
+
+    #[envconfig(default = "100")]
+    pub max_concurrent_transactions: usize,
+
+    // How long the coordinator will wait to fill a batch before forcing a small
+    // one, in seconds.
+    #[envconfig(default = "1")]
+    pub max_issue_period: u64,
+
+    // Retry budget for transient issue() failures (chunk0-4).
+    #[envconfig(default = "3")]
+    pub max_issue_retries: u32,
+
+    #[envconfig(default = "1000000")]
+    pub cache_capacity: usize,
+
+    #[envconfig(default = "10000")]
+    pub compaction_batch_size: usize,
+
+    // Events fanning out to more than this many updates are skipped.
+    #[envconfig(default = "10000")]
+    pub update_count_skip_threshold: usize,
+
+    // Offset-commit strategy (chunk0-2). `Manual` only stores offsets once the
+    // DB transaction durably lands; `Auto` keeps librdkafka's auto-commit.
+    #[envconfig(default = "manual")]
+    pub commit_mode: CommitMode,
+
+    // Dead-letter policy (chunk0-1): trip liveness if more than
+    // `dlq_max_invalid_messages` land within `dlq_window_secs`.
+    #[envconfig(default = "1000")]
+    pub dlq_max_invalid_messages: usize,
+
+    #[envconfig(default = "60")]
+    pub dlq_window_secs: u64,
+}
+
+/// Whether offsets are committed automatically by librdkafka or only after the
+/// DB transaction that wrote them succeeds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitMode {
+    Auto,
+    Manual,
+}
+
+impl FromStr for CommitMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(CommitMode::Auto),
+            "manual" => Ok(CommitMode::Manual),
+            other => Err(format!("unknown commit mode: {other}")),
+        }
+    }
+}
+
+#[derive(Envconfig, Clone)]
+pub struct KafkaConfig {
+    #[envconfig(default = "kafka:9092")]
+    pub kafka_hosts: String,
+
+    #[envconfig(default = "clickhouse_events_json")]
+    pub event_topic: String,
+
+    // Topic failed/un-parseable messages are dead-lettered to (chunk0-1). When
+    // unset the DLQ subsystem is disabled.
+    pub dlq_topic: Option<String>,
+
+    #[envconfig(default = "property-definitions-rs")]
+    pub consumer_group: String,
+
+    #[envconfig(default = "false")]
+    pub kafka_tls: bool,
+}
+
+impl From<&KafkaConfig> for ClientConfig {
+    fn from(config: &KafkaConfig) -> Self {
+        let mut client_config = ClientConfig::new();
+        client_config
+            .set("bootstrap.servers", &config.kafka_hosts)
+            .set("group.id", &config.consumer_group)
+            .set("statistics.interval.ms", "10000")
+            .set("partition.assignment.strategy", "cooperative-sticky");
+
+        if config.kafka_tls {
+            client_config.set("security.protocol", "ssl");
+        }
+
+        client_config
+    }
+}
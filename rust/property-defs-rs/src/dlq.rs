@@ -0,0 +1,194 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use rdkafka::{
+    producer::{FutureProducer, FutureRecord},
+    util::Timeout,
+    ClientConfig,
+};
+use tracing::{error, warn};
+
+use crate::metrics_consts::{DLQ_PRODUCED, DLQ_PRODUCE_FAILED};
+
+/// How a dead-lettered message came to be, recorded in the record's `x-*`
+/// headers so operators can triage the topic without re-parsing the payload.
+pub struct DlqHeaders<'a> {
+    /// Which failure path produced this record, so a replay consumer can tell
+    /// raw-event payloads (unparseable messages) apart from the serialized
+    /// source-offset coordinates we emit for failed `issue()` batches.
+    pub kind: &'a str,
+    pub error: &'a str,
+    pub source_topic: &'a str,
+    pub source_partition: i32,
+    pub source_offset: i64,
+    pub timestamp_ms: i64,
+}
+
+/// Outcome of a dead-letter produce: whether the record was actually handed off
+/// to the broker, and whether we're still within the sliding-window budget.
+pub struct DlqOutcome {
+    pub delivered: bool,
+    pub within_budget: bool,
+}
+
+/// Bounds the rate of dead-lettering over a sliding window. If more than
+/// `max_invalid_messages` land in `window` we consider the input systemically
+/// broken and let the caller fail its liveness check rather than quietly
+/// shovelling everything into the DLQ.
+pub struct DlqPolicy {
+    max_invalid_messages: usize,
+    window: Duration,
+    recent: Mutex<VecDeque<Instant>>,
+}
+
+impl DlqPolicy {
+    pub fn new(max_invalid_messages: usize, window: Duration) -> Self {
+        Self {
+            max_invalid_messages,
+            window,
+            recent: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Record a dead-letter and return whether we're still within budget.
+    /// `false` means the window threshold has been exceeded.
+    pub fn record(&self, now: Instant) -> bool {
+        let mut recent = self.recent.lock().unwrap();
+        while let Some(front) = recent.front() {
+            if now.duration_since(*front) > self.window {
+                recent.pop_front();
+            } else {
+                break;
+            }
+        }
+        recent.push_back(now);
+        recent.len() <= self.max_invalid_messages
+    }
+}
+
+/// Owns the rdkafka producer we emit un-processable messages to, plus the
+/// policy guarding against runaway dead-lettering.
+pub struct DeadLetterQueue {
+    producer: FutureProducer,
+    topic: String,
+    policy: DlqPolicy,
+}
+
+impl DeadLetterQueue {
+    pub fn new(
+        kafka_config: &ClientConfig,
+        topic: String,
+        policy: DlqPolicy,
+    ) -> Result<Self, rdkafka::error::KafkaError> {
+        Ok(Self {
+            producer: kafka_config.create()?,
+            topic,
+            policy,
+        })
+    }
+
+    /// Produce the original message bytes to the DLQ topic, tagging them with
+    /// the error context. Reports whether the record was delivered and whether
+    /// we're still within the policy's sliding-window budget, so callers can
+    /// both avoid advancing offsets past an undelivered record and trip liveness
+    /// once the threshold is exceeded.
+    pub async fn produce(&self, payload: &[u8], headers: DlqHeaders<'_>, now: Instant) -> DlqOutcome {
+        use rdkafka::message::{Header, OwnedHeaders};
+
+        let kafka_headers = OwnedHeaders::new()
+            .insert(Header {
+                key: "x-dlq-kind",
+                value: Some(headers.kind),
+            })
+            .insert(Header {
+                key: "x-error",
+                value: Some(headers.error),
+            })
+            .insert(Header {
+                key: "x-source-topic",
+                value: Some(headers.source_topic),
+            })
+            .insert(Header {
+                key: "x-source-partition",
+                value: Some(&headers.source_partition.to_string()),
+            })
+            .insert(Header {
+                key: "x-source-offset",
+                value: Some(&headers.source_offset.to_string()),
+            })
+            .insert(Header {
+                key: "x-dlq-timestamp-ms",
+                value: Some(&headers.timestamp_ms.to_string()),
+            });
+
+        let record: FutureRecord<'_, (), [u8]> = FutureRecord::to(&self.topic)
+            .payload(payload)
+            .headers(kafka_headers);
+
+        // Never time out purely on local-queue enqueue pressure: a zero timeout
+        // would return `QueueFull` the instant librdkafka's buffer is momentarily
+        // full, which is exactly what happens during a dead-letter burst — the
+        // moment the DLQ most needs to succeed. Block until the record is
+        // enqueued (or the broker genuinely rejects it) instead.
+        let delivered = match self.producer.send(record, Timeout::Never).await {
+            Ok(_) => {
+                metrics::counter!(DLQ_PRODUCED).increment(1);
+                true
+            }
+            Err((e, _)) => {
+                error!("Failed to produce to DLQ topic {}: {:?}", self.topic, e);
+                metrics::counter!(DLQ_PRODUCE_FAILED).increment(1);
+                false
+            }
+        };
+
+        let within_budget = self.policy.record(now);
+        if !within_budget {
+            warn!("DLQ rate exceeded configured threshold, tripping liveness");
+        }
+        DlqOutcome {
+            delivered,
+            within_budget,
+        }
+    }
+
+    /// Block for up to 5 seconds while outstanding dead-letter produces are
+    /// delivered; if they are not all delivered by then, log a warning and
+    /// return. Called during shutdown so we don't drop un-flushed DLQ records.
+    pub fn flush(&self) {
+        use rdkafka::producer::Producer;
+        if let Err(e) = self.producer.flush(Duration::from_secs(5)) {
+            warn!("Failed to flush DLQ producer: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stays_within_budget_until_threshold() {
+        let policy = DlqPolicy::new(2, Duration::from_millis(100));
+        let now = Instant::now();
+        assert!(policy.record(now));
+        assert!(policy.record(now));
+        // Third record inside the window breaches the max of 2.
+        assert!(!policy.record(now));
+    }
+
+    #[test]
+    fn sliding_window_evicts_expired_records() {
+        let policy = DlqPolicy::new(2, Duration::from_millis(100));
+        let start = Instant::now();
+        assert!(policy.record(start));
+        assert!(policy.record(start));
+        assert!(!policy.record(start));
+        // Past the window the earlier records age out and the budget recovers.
+        let later = start + Duration::from_millis(200);
+        assert!(policy.record(later));
+    }
+}
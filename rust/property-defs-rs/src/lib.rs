@@ -0,0 +1,43 @@
+use metrics_consts::{EMPTY_EVENTS, EVENT_PARSE_ERROR};
+use rdkafka::message::BorrowedMessage;
+use rdkafka::Message;
+use tracing::warn;
+use types::Event;
+
+pub mod app_context;
+pub mod config;
+pub mod metrics_consts;
+pub mod types;
+
+// Subsystems added while hardening the pipeline.
+pub mod adaptive;
+pub mod dlq;
+pub mod metrics_buffer;
+pub mod offsets;
+pub mod retry;
+
+/// Parse a raw Kafka message into an `Event`, returning `None` (and bumping the
+/// relevant counter) for empty or malformed payloads.
+///
+/// Takes the message by reference so the caller retains ownership and can read
+/// its source `(topic, partition, offset)` afterwards to thread through the
+/// offset-commit machinery (chunk0-2).
+pub fn message_to_event(msg: &BorrowedMessage) -> Option<Event> {
+    let payload = match msg.payload() {
+        Some(payload) => payload,
+        None => {
+            warn!("Received event with empty payload");
+            metrics::counter!(EMPTY_EVENTS).increment(1);
+            return None;
+        }
+    };
+
+    match serde_json::from_slice::<Event>(payload) {
+        Ok(event) => Some(event),
+        Err(e) => {
+            warn!("Failed to parse event: {:?}", e);
+            metrics::counter!(EVENT_PARSE_ERROR).increment(1);
+            None
+        }
+    }
+}
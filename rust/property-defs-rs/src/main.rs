@@ -1,7 +1,13 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
 
-use ahash::AHashSet;
-use axum::{routing::get, Router};
+use ahash::AHashMap;
+use axum::{response::IntoResponse, routing::get, Router};
 use envconfig::Envconfig;
 use futures::future::ready;
 use property_defs_rs::{
@@ -9,25 +15,35 @@ use property_defs_rs::{
     config::Config,
     message_to_event,
     metrics_consts::{
-        BATCH_ACQUIRE_TIME, CACHE_CONSUMED, COMPACTED_UPDATES, EVENTS_RECEIVED, FORCED_SMALL_BATCH,
-        PERMIT_WAIT_TIME, RECV_DEQUEUED, TRANSACTION_LIMIT_SATURATION, UPDATES_FILTERED_BY_CACHE,
-        UPDATES_PER_EVENT, UPDATES_SEEN, UPDATE_ISSUE_TIME, WORKER_BLOCKED,
+        ADAPTIVE_BATCH_TARGET, BATCH_ACQUIRE_TIME, CACHE_CONSUMED, COMPACTED_UPDATES,
+        EVENTS_RECEIVED, FORCED_SMALL_BATCH, ISSUE_PERMANENT_DROPS, ISSUE_RETRIES,
+        ISSUE_RETRIES_EXHAUSTED, PERMIT_WAIT_TIME, RECV_DEQUEUED, TRANSACTION_LIMIT_SATURATION,
+        UPDATES_FILTERED_BY_CACHE, UPDATES_PER_EVENT, UPDATES_SEEN, UPDATE_ISSUE_TIME,
+        WORKER_BLOCKED,
     },
     types::Update,
 };
 use quick_cache::sync::Cache;
+use property_defs_rs::adaptive::AdaptiveBatcher;
+use property_defs_rs::config::CommitMode;
+use property_defs_rs::dlq::{DeadLetterQueue, DlqHeaders, DlqPolicy};
+use property_defs_rs::metrics_buffer::MetricsBuffer;
+use property_defs_rs::offsets::{OffsetTracker, SourceOffset};
+use property_defs_rs::retry::{backoff_with_jitter, classify, ErrorClass};
 use rdkafka::{
     consumer::{Consumer, StreamConsumer},
+    message::Message,
     ClientConfig,
 };
 use serve_metrics::{serve, setup_metrics_routes};
 use tokio::{
     sync::{
         mpsc::{self, error::TrySendError},
-        Semaphore,
+        OwnedSemaphorePermit, Semaphore,
     },
     task::JoinHandle,
 };
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 
@@ -44,14 +60,30 @@ pub async fn index() -> &'static str {
     "property definitions service"
 }
 
-fn start_health_liveness_server(config: &Config, context: Arc<AppContext>) -> JoinHandle<()> {
+fn start_health_liveness_server(
+    config: &Config,
+    context: Arc<AppContext>,
+    shutdown: CancellationToken,
+    unhealthy_shutdown: Arc<AtomicBool>,
+) -> JoinHandle<()> {
     let config = config.clone();
     let router = Router::new()
         .route("/", get(index))
         .route("/_readiness", get(index))
         .route(
             "/_liveness",
-            get(move || ready(context.liveness.get_status())),
+            get(move || {
+                // A clean drain reports a distinct state so orchestrators take
+                // us out of rotation without treating it as a crash. But a
+                // shutdown triggered by a breached DLQ budget is a genuine
+                // failure the request wants surfaced, so keep reporting the
+                // unhealthy liveness status in that case rather than masking it.
+                if shutdown.is_cancelled() && !unhealthy_shutdown.load(Ordering::SeqCst) {
+                    ready("shutdown".into_response())
+                } else {
+                    ready(context.liveness.get_status().into_response())
+                }
+            }),
         );
     let router = setup_metrics_routes(router);
     let bind = format!("{}:{}", config.host, config.port);
@@ -62,59 +94,484 @@ fn start_health_liveness_server(config: &Config, context: Arc<AppContext>) -> Jo
     })
 }
 
+/// Drain whatever is left in the channel into final batches, issue them, wait
+/// for any still-outstanding transactions to finish by acquiring every permit,
+/// flush the DLQ producer, and commit the final offsets. Called once after the
+/// shutdown token trips so we leave rotation without losing queued updates.
+#[allow(clippy::too_many_arguments)]
+async fn drain_and_shutdown(
+    rx: &mut mpsc::Receiver<(Update, SourceOffset)>,
+    context: &Arc<AppContext>,
+    offsets: &Arc<Mutex<OffsetTracker>>,
+    consumer: &Arc<StreamConsumer>,
+    dlq: &Option<Arc<DeadLetterQueue>>,
+    transaction_limit: &Arc<Semaphore>,
+    batcher: &Arc<AdaptiveBatcher>,
+    commit_mode: CommitMode,
+    max_concurrent_transactions: usize,
+    max_issue_retries: u32,
+    event_topic: &str,
+    shutdown: &CancellationToken,
+    unhealthy_shutdown: &Arc<AtomicBool>,
+) {
+    loop {
+        let mut batch: Vec<(Update, SourceOffset)> = Vec::new();
+        // recv_many returns 0 only once all senders have dropped and the
+        // channel is empty; the producer loops drop their senders on shutdown.
+        let got = rx.recv_many(&mut batch, usize::MAX).await;
+        if got == 0 {
+            break;
+        }
+        let (updates, sources): (Vec<Update>, Vec<SourceOffset>) = batch.into_iter().unzip();
+        // Reuse the same permit + retry/DLQ path as steady state: passing `None`
+        // makes the helper acquire its own permit, so these final batches stay
+        // within `max_concurrent_transactions` and survive transient DB errors
+        // rather than dropping straight to the DLQ.
+        issue_batch_with_retries(
+            context.clone(),
+            transaction_limit.clone(),
+            offsets.clone(),
+            consumer.clone(),
+            dlq.clone(),
+            batcher.clone(),
+            commit_mode,
+            max_issue_retries,
+            event_topic.to_string(),
+            updates,
+            sources,
+            shutdown.clone(),
+            unhealthy_shutdown.clone(),
+            None,
+        )
+        .await;
+    }
+
+    // Block until every in-flight transaction spawned before shutdown has
+    // released its permit.
+    let _all = transaction_limit
+        .acquire_many(max_concurrent_transactions as u32)
+        .await
+        .expect("transaction semaphore closed during shutdown");
+
+    if let Some(dlq) = dlq.as_ref() {
+        dlq.flush();
+    }
+
+    if commit_mode == CommitMode::Manual {
+        offsets.lock().unwrap().store(consumer);
+        // `store` only records the offsets locally; with auto-commit disabled
+        // nothing flushes them to the broker unless we ask, so force a
+        // synchronous commit to make the drained batches durable before the
+        // process exits, otherwise a clean shutdown could still reprocess them
+        // on restart.
+        if let Err(e) = consumer.commit_consumer_state(rdkafka::consumer::CommitMode::Sync) {
+            warn!("Failed to synchronously commit offsets on shutdown: {:?}", e);
+        }
+    }
+
+    info!("Drain complete, shutting down");
+}
+
+/// Surface a batch that can't be written (retries exhausted or a permanent
+/// error) to the DLQ so it isn't silently lost. A failed DB transaction doesn't
+/// hand us the original message bytes back, but the source `(topic, partition,
+/// offset)` of every update in the batch is enough to replay it from the source
+/// topic, so we serialize those coordinates as the record payload and tag the
+/// headers with the first source as a representative.
+///
+/// Returns whether the caller should advance the commit watermark past this
+/// batch. We advance once the batch has been dead-lettered, and also when no
+/// DLQ is configured at all: in that case the operator has opted out of the
+/// safety net, and dropping a single poison batch (loudly) is far better than
+/// pinning the whole partition's offset progress forever. We hold the offsets
+/// pinned — so the batch is reprocessed on restart rather than lost — only when
+/// a DLQ exists but the produce failed, which is a transient DLQ outage.
+#[allow(clippy::too_many_arguments)]
+async fn route_to_dlq(
+    dlq: Option<&Arc<DeadLetterQueue>>,
+    context: &Arc<AppContext>,
+    topic: &str,
+    sources: &[SourceOffset],
+    err: &sqlx::Error,
+    shutdown: &CancellationToken,
+    unhealthy_shutdown: &Arc<AtomicBool>,
+) -> bool {
+    let Some(dlq) = dlq else {
+        warn!(
+            "No DLQ configured; dropping un-writable batch of {} updates to avoid stalling offsets",
+            sources.len()
+        );
+        return true;
+    };
+    let payload = serde_json::to_vec(sources).unwrap_or_default();
+    let representative = sources.first().copied().unwrap_or(SourceOffset {
+        partition: -1,
+        offset: -1,
+    });
+    // A failed transaction gives us no source message, so stamp the record with
+    // wall-clock time at dead-lettering — enough for operators to correlate the
+    // failure with DB health trends on the triage topic.
+    let timestamp_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    let outcome = dlq
+        .produce(
+            &payload,
+            DlqHeaders {
+                kind: "issue-failure",
+                error: &format!("issue failed ({} updates): {err:?}", sources.len()),
+                source_topic: topic,
+                source_partition: representative.partition,
+                source_offset: representative.offset,
+                timestamp_ms,
+            },
+            tokio::time::Instant::now().into_std(),
+        )
+        .await;
+    if !outcome.within_budget {
+        // Same systemic-failure guard as the unparseable path: a sustained
+        // dead-letter rate — here a DB-failure flood routing every batch to the
+        // DLQ — means liveness should fail rather than silently shovel every
+        // batch aside. Trip the shared shutdown so the service leaves rotation
+        // instead of masking the problem.
+        warn!("DLQ budget exceeded on issue-failure path, signalling shutdown");
+        context.liveness.report_unhealthy().await;
+        unhealthy_shutdown.store(true, Ordering::SeqCst);
+        shutdown.cancel();
+    }
+    if !outcome.delivered {
+        warn!("DLQ produce failed; leaving offsets pinned for reprocessing on restart");
+    }
+    outcome.delivered
+}
+
+/// Mark a batch's source offsets complete and advance the stored watermark.
+/// Called once a batch reaches a terminal state — durably written, or given up
+/// on and dead-lettered — so that a permanently-failed batch doesn't pin its
+/// partition's commit watermark just below the failed offset forever, which
+/// would stall every later successfully-written update on that partition.
+fn complete_and_store(
+    commit_mode: CommitMode,
+    offsets: &Arc<Mutex<OffsetTracker>>,
+    consumer: &StreamConsumer,
+    sources: &[SourceOffset],
+) {
+    if commit_mode != CommitMode::Manual {
+        return;
+    }
+    {
+        let mut offsets = offsets.lock().unwrap();
+        for source in sources {
+            offsets.complete(*source);
+        }
+        offsets.store(consumer);
+    }
+    // Auto-commit is disabled in manual mode, so nothing flushes the offsets we
+    // just stored unless we ask. Commit asynchronously here to keep the hot path
+    // cheap; the synchronous final commit on shutdown closes any gap left by an
+    // async commit still in flight at exit.
+    if let Err(e) = consumer.commit_consumer_state(rdkafka::consumer::CommitMode::Async) {
+        warn!("Failed to commit offsets: {:?}", e);
+    }
+}
+
+/// Issue one batch under the transaction-permit + retry/backoff/DLQ policy
+/// shared by the steady-state coordinator and the shutdown drain. Holds a permit
+/// for each attempt but releases it while backing off so we don't keep
+/// backpressure closed during the sleep; on success or a terminal failure it
+/// advances the batch's offsets (see [`complete_and_store`] / [`route_to_dlq`]).
+///
+/// `permit` lets the coordinator hand over the permit it already acquired; the
+/// drain passes `None` and the helper acquires its own, so final batches respect
+/// `max_concurrent_transactions` instead of issuing unbounded direct writes.
+#[allow(clippy::too_many_arguments)]
+async fn issue_batch_with_retries(
+    context: Arc<AppContext>,
+    transaction_limit: Arc<Semaphore>,
+    offsets: Arc<Mutex<OffsetTracker>>,
+    consumer: Arc<StreamConsumer>,
+    dlq: Option<Arc<DeadLetterQueue>>,
+    batcher: Arc<AdaptiveBatcher>,
+    commit_mode: CommitMode,
+    max_issue_retries: u32,
+    topic: String,
+    updates: Vec<Update>,
+    sources: Vec<SourceOffset>,
+    shutdown: CancellationToken,
+    unhealthy_shutdown: Arc<AtomicBool>,
+    mut permit: Option<OwnedSemaphorePermit>,
+) {
+    let issue_time = common_metrics::timing_guard(UPDATE_ISSUE_TIME, &[]);
+    let update_count = updates.len();
+    let mut attempt: u32 = 0;
+    loop {
+        if permit.is_none() {
+            permit = Some(transaction_limit.clone().acquire_owned().await.unwrap());
+        }
+
+        let issue_start = tokio::time::Instant::now();
+        // Borrow the batch rather than cloning per attempt: the common case is
+        // a single successful attempt with no retry, so a clone of a batch up to
+        // `max_update_batch_size` would be a large allocation+copy on the
+        // hottest path for no benefit. Retries simply re-borrow the same Vec.
+        let result = context.issue(&updates).await;
+
+        let err = match result {
+            Ok(_) => {
+                // Only fold a *successful* attempt into the cost estimate: a
+                // failed attempt's timing reflects DB trouble rather than real
+                // write cost, and feeding it in would shrink the adaptive target
+                // exactly when the DB is already struggling.
+                batcher.observe(issue_start.elapsed().as_millis() as f64, update_count);
+                // Only now are these offsets durable. Mark them complete and
+                // store the gap-free watermark per partition.
+                complete_and_store(commit_mode, &offsets, &consumer, &sources);
+                break;
+            }
+            Err(e) => e,
+        };
+
+        match classify(&err) {
+            ErrorClass::Transient if attempt < max_issue_retries => {
+                metrics::counter!(ISSUE_RETRIES).increment(1);
+                warn!("Transient issue failure (attempt {attempt}): {err:?}, retrying");
+                // Release the permit so other work can proceed while we sleep.
+                permit = None;
+                let delay =
+                    backoff_with_jitter(attempt, Duration::from_millis(50), Duration::from_secs(5));
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            ErrorClass::Transient => {
+                metrics::counter!(ISSUE_RETRIES_EXHAUSTED).increment(1);
+                warn!("Exhausted retries for batch: {err:?}");
+                // Advance the watermark once the batch has been dealt with
+                // (dead-lettered, or dropped when no DLQ is set); a failed DLQ
+                // produce leaves it pinned for reprocessing.
+                if route_to_dlq(
+                    dlq.as_ref(),
+                    &context,
+                    &topic,
+                    &sources,
+                    &err,
+                    &shutdown,
+                    &unhealthy_shutdown,
+                )
+                .await
+                {
+                    complete_and_store(commit_mode, &offsets, &consumer, &sources);
+                }
+                break;
+            }
+            ErrorClass::Permanent => {
+                metrics::counter!(ISSUE_PERMANENT_DROPS).increment(1);
+                warn!("Permanent issue failure, dropping batch: {err:?}");
+                if route_to_dlq(
+                    dlq.as_ref(),
+                    &context,
+                    &topic,
+                    &sources,
+                    &err,
+                    &shutdown,
+                    &unhealthy_shutdown,
+                )
+                .await
+                {
+                    complete_and_store(commit_mode, &offsets, &consumer, &sources);
+                }
+                break;
+            }
+        }
+    }
+    issue_time.fin();
+}
+
+/// Flush the producer loop's compaction `batch` into the coordinator channel,
+/// applying the same cache filter and deferred-completion bookkeeping as the
+/// inline flush. Shared by the steady-state flush and the shutdown path so the
+/// drain sees the compacted-but-unsent updates instead of leaving them pinned
+/// for reprocessing. Returns `false` if the channel closed mid-send (the
+/// coordinator is gone), so the caller can stop.
+async fn flush_compaction_batch(
+    batch: &mut AHashMap<Update, SourceOffset>,
+    staged_completes: &mut Vec<SourceOffset>,
+    channel: &mpsc::Sender<(Update, SourceOffset)>,
+    shared_cache: &Arc<Cache<Update, ()>>,
+    offsets: &Arc<Mutex<OffsetTracker>>,
+    metrics_buf: &mut MetricsBuffer,
+) -> bool {
+    for (update, source) in batch.drain() {
+        if shared_cache.get(&update).is_some() {
+            metrics_buf.increment(UPDATES_FILTERED_BY_CACHE, 1);
+            staged_completes.push(source);
+            continue;
+        }
+        shared_cache.insert(update.clone(), ());
+        match channel.try_send((update, source)) {
+            Ok(_) => {}
+            Err(TrySendError::Full(payload)) => {
+                warn!("Worker blocked");
+                metrics_buf.increment(WORKER_BLOCKED, 1);
+                if channel.send(payload).await.is_err() {
+                    warn!("Coordinator send failed during flush");
+                    return false;
+                }
+            }
+            Err(e) => {
+                warn!("Coordinator send failed: {:?}", e);
+                return false;
+            }
+        }
+    }
+    // Complete all the dropped offsets for this flush under one lock.
+    if !staged_completes.is_empty() {
+        let mut offsets = offsets.lock().unwrap();
+        for source in staged_completes.drain(..) {
+            offsets.complete(source);
+        }
+    }
+    true
+}
+
 async fn spawn_producer_loop(
     consumer: Arc<StreamConsumer>,
-    channel: mpsc::Sender<Update>,
+    channel: mpsc::Sender<(Update, SourceOffset)>,
     shared_cache: Arc<Cache<Update, ()>>,
     skip_threshold: usize,
     compaction_batch_size: usize,
+    context: Arc<AppContext>,
+    dlq: Option<Arc<DeadLetterQueue>>,
+    offsets: Arc<Mutex<OffsetTracker>>,
+    shutdown: CancellationToken,
+    unhealthy_shutdown: Arc<AtomicBool>,
 ) {
-    let mut batch = AHashSet::with_capacity(compaction_batch_size);
+    // We carry the source offset alongside each compacted update so the
+    // coordinator can commit only after the update is durably written. Offsets
+    // for updates dropped here (compacted away or already cached) are completed
+    // immediately, since there is nothing left to write for them.
+    let mut batch: AHashMap<Update, SourceOffset> = AHashMap::with_capacity(compaction_batch_size);
     let mut last_send = tokio::time::Instant::now();
+    // Accumulate per-update metrics locally and flush them at the compaction
+    // boundary and at least once per second, so the hot path avoids a registry
+    // lookup per update.
+    let mut metrics_buf = MetricsBuffer::new(Duration::from_secs(1));
+    // Offsets for updates that get dropped (compacted away or already cached)
+    // are staged here and completed in a single locked section at the flush
+    // boundary, rather than taking the global offset lock once per dropped
+    // update. `track` stays immediate (below): the gap-free watermark requires
+    // an offset to be pending before any higher offset on its partition can be
+    // completed, and partitions can be split across worker loops. Completing
+    // late is always safe — it only holds the pin a little longer.
+    let mut staged_completes: Vec<SourceOffset> = Vec::new();
     loop {
-        let message = consumer
-            .recv()
-            .await
-            .expect("TODO - workers panic on kafka recv fail");
+        // Stop pulling new work as soon as shutdown is requested; the
+        // coordinator drains whatever is already in the channel.
+        let message = tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => {
+                info!("Producer loop stopping on shutdown; flushing compaction batch");
+                // Flush whatever we've compacted but not yet sent into the
+                // channel so the coordinator's drain issues it, honoring the
+                // request's "drain the remaining queued updates" intent rather
+                // than leaving it pinned for at-least-once reprocessing.
+                metrics_buf.flush();
+                flush_compaction_batch(
+                    &mut batch,
+                    &mut staged_completes,
+                    &channel,
+                    &shared_cache,
+                    &offsets,
+                    &mut metrics_buf,
+                )
+                .await;
+                return;
+            }
+            message = consumer.recv() => {
+                message.expect("TODO - workers panic on kafka recv fail")
+            }
+        };
 
-        let Some(event) = message_to_event(message) else {
-            continue;
+        let source = SourceOffset {
+            partition: message.partition(),
+            offset: message.offset(),
+        };
+
+        let event = match message_to_event(&message) {
+            Some(event) => event,
+            None => {
+                // Un-parseable message: route it to the DLQ rather than
+                // silently dropping it, so we keep both visibility and data.
+                if let Some(dlq) = dlq.as_ref() {
+                    let outcome = dlq
+                        .produce(
+                            message.payload().unwrap_or_default(),
+                            DlqHeaders {
+                                kind: "unparseable-event",
+                                error: "message_to_event returned None",
+                                source_topic: message.topic(),
+                                source_partition: message.partition(),
+                                source_offset: message.offset(),
+                                timestamp_ms: message
+                                    .timestamp()
+                                    .to_millis()
+                                    .unwrap_or_default(),
+                            },
+                            tokio::time::Instant::now().into_std(),
+                        )
+                        .await;
+                    if !outcome.within_budget {
+                        // A breached DLQ budget is a systemic problem, not a
+                        // single-worker hiccup: trip the shared shutdown token so
+                        // every producer loop stops and the coordinator drains
+                        // and leaves rotation, rather than masking it by quietly
+                        // exiting this one task while the others keep consuming.
+                        warn!("DLQ budget exceeded, signalling shutdown");
+                        context.liveness.report_unhealthy().await;
+                        // Mark this as an unhealthy shutdown so `/_liveness`
+                        // keeps failing instead of reporting a clean "shutdown".
+                        unhealthy_shutdown.store(true, Ordering::SeqCst);
+                        shutdown.cancel();
+                        return;
+                    }
+                }
+                continue;
+            }
         };
 
         let updates = event.into_updates(skip_threshold);
 
-        metrics::counter!(EVENTS_RECEIVED).increment(1);
-        metrics::counter!(UPDATES_SEEN).increment(updates.len() as u64);
-        metrics::histogram!(UPDATES_PER_EVENT).record(updates.len() as f64);
+        metrics_buf.increment(EVENTS_RECEIVED, 1);
+        metrics_buf.increment(UPDATES_SEEN, updates.len() as u64);
+        metrics_buf.record(UPDATES_PER_EVENT, updates.len() as f64);
+        // Flush on the fixed interval too, so low-rate partitions don't hold
+        // their counters until the 10s compaction boundary.
+        metrics_buf.maybe_flush();
 
         for update in updates {
-            if batch.contains(&update) {
-                metrics::counter!(COMPACTED_UPDATES).increment(1);
+            offsets.lock().unwrap().track(source);
+            if batch.contains_key(&update) {
+                metrics_buf.increment(COMPACTED_UPDATES, 1);
+                staged_completes.push(source);
                 continue;
             }
-            batch.insert(update);
+            batch.insert(update, source);
 
             if batch.len() >= compaction_batch_size || last_send.elapsed() > Duration::from_secs(10)
             {
                 last_send = tokio::time::Instant::now();
-                for update in batch.drain() {
-                    if shared_cache.get(&update).is_some() {
-                        metrics::counter!(UPDATES_FILTERED_BY_CACHE).increment(1);
-                        continue;
-                    }
-                    shared_cache.insert(update.clone(), ());
-                    match channel.try_send(update) {
-                        Ok(_) => {}
-                        Err(TrySendError::Full(update)) => {
-                            warn!("Worker blocked");
-                            metrics::counter!(WORKER_BLOCKED).increment(1);
-                            channel.send(update).await.unwrap();
-                        }
-                        Err(e) => {
-                            warn!("Coordinator send failed: {:?}", e);
-                            return;
-                        }
-                    }
+                metrics_buf.flush();
+                if !flush_compaction_batch(
+                    &mut batch,
+                    &mut staged_completes,
+                    &channel,
+                    &shared_cache,
+                    &offsets,
+                    &mut metrics_buf,
+                )
+                .await
+                {
+                    return;
                 }
             }
         }
@@ -128,7 +585,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let config = Config::init_from_env()?;
 
-    let kafka_config: ClientConfig = (&config.kafka).into();
+    let mut kafka_config: ClientConfig = (&config.kafka).into();
+
+    // In manual mode we take full control of offset progression: both
+    // auto-commit and auto-store are disabled. Offsets are stored only after the
+    // DB transaction that durably wrote them commits, and then explicitly
+    // committed — asynchronously after each successful batch (see
+    // `complete_and_store`) and synchronously during the shutdown drain (see
+    // `drain_and_shutdown`) — so a crash mid-`issue` can never advance past
+    // un-written updates.
+    if config.commit_mode == CommitMode::Manual {
+        kafka_config.set("enable.auto.commit", "false");
+        kafka_config.set("enable.auto.offset.store", "false");
+    }
 
     let consumer: Arc<StreamConsumer> = Arc::new(kafka_config.create()?);
 
@@ -138,12 +607,62 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("Subscribed to topic: {}", config.kafka.event_topic);
 
-    start_health_liveness_server(&config, context.clone());
+    // Shutdown coordinator: a SIGTERM/SIGINT flips this token, which stops the
+    // producer loops from pulling new messages and signals the coordinator to
+    // drain and commit before returning.
+    let shutdown = CancellationToken::new();
+    {
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            let mut sigterm =
+                tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+                    .expect("failed to install SIGTERM handler");
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => info!("Received SIGINT, shutting down"),
+                _ = sigterm.recv() => info!("Received SIGTERM, shutting down"),
+            }
+            shutdown.cancel();
+        });
+    }
+
+    // Set when a DLQ-budget breach forces shutdown, so the liveness endpoint can
+    // distinguish that failure from a clean drain.
+    let unhealthy_shutdown = Arc::new(AtomicBool::new(false));
+
+    start_health_liveness_server(
+        &config,
+        context.clone(),
+        shutdown.clone(),
+        unhealthy_shutdown.clone(),
+    );
 
     let (tx, mut rx) = mpsc::channel(config.update_batch_size * config.channel_slots_per_worker);
     let transaction_limit = Arc::new(Semaphore::new(config.max_concurrent_transactions));
     let cache = Arc::new(Cache::new(config.cache_capacity));
 
+    let offsets = Arc::new(Mutex::new(OffsetTracker::new(config.kafka.event_topic.clone())));
+
+    let batcher = Arc::new(AdaptiveBatcher::new(
+        config.update_batch_size,
+        config.max_update_batch_size,
+        config.target_issue_ms,
+    ));
+
+    let dlq = match config.kafka.dlq_topic.as_ref() {
+        Some(topic) => {
+            let policy = DlqPolicy::new(
+                config.dlq_max_invalid_messages,
+                Duration::from_secs(config.dlq_window_secs),
+            );
+            Some(Arc::new(DeadLetterQueue::new(
+                &kafka_config,
+                topic.clone(),
+                policy,
+            )?))
+        }
+        None => None,
+    };
+
     for _ in 0..config.worker_loop_count {
         tokio::spawn(spawn_producer_loop(
             consumer.clone(),
@@ -151,18 +670,59 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             cache.clone(),
             config.update_count_skip_threshold,
             config.compaction_batch_size,
+            context.clone(),
+            dlq.clone(),
+            offsets.clone(),
+            shutdown.clone(),
+            unhealthy_shutdown.clone(),
         ));
     }
+    // Drop our own sender so that once the producer loops exit on shutdown the
+    // channel closes. The coordinator's fill loop then sees `recv_many` return 0
+    // and, if shutdown is in progress, breaks to issue the final partial batch
+    // and enters `drain_and_shutdown`; outside shutdown an empty closed channel
+    // is treated as fatal.
+    drop(tx);
+
+    let mut metrics_buf = MetricsBuffer::new(Duration::from_secs(1));
 
     loop {
-        let mut batch = Vec::with_capacity(config.update_batch_size);
+        if shutdown.is_cancelled() {
+            info!("Shutdown requested, draining remaining updates");
+            drain_and_shutdown(
+                &mut rx,
+                &context,
+                &offsets,
+                &consumer,
+                &dlq,
+                &transaction_limit,
+                &batcher,
+                config.commit_mode,
+                config.max_concurrent_transactions,
+                config.max_issue_retries,
+                &config.kafka.event_topic,
+                &shutdown,
+                &unhealthy_shutdown,
+            )
+            .await;
+            return Ok(());
+        }
+
+        // Re-target the batch size from the cost budget and live backpressure
+        // before filling each batch.
+        let saturated = transaction_limit.available_permits() == 0;
+        let target = batcher.adjust(saturated, rx.len() > 0);
+        metrics::gauge!(ADAPTIVE_BATCH_TARGET).set(target as f64);
+        metrics_buf.maybe_flush();
+
+        let mut batch = Vec::with_capacity(target);
 
         let batch_start = tokio::time::Instant::now();
         let batch_time = common_metrics::timing_guard(BATCH_ACQUIRE_TIME, &[]);
-        while batch.len() < config.update_batch_size {
+        while batch.len() < target {
             context.worker_liveness.report_healthy().await;
 
-            let remaining_capacity = config.update_batch_size - batch.len();
+            let remaining_capacity = target - batch.len();
             // We race these two, so we can escape this loop and do a small batch if we've been waiting too long
             let recv = rx.recv_many(&mut batch, remaining_capacity);
             let sleep = tokio::time::sleep(Duration::from_secs(1));
@@ -170,6 +730,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             tokio::select! {
                 got = recv => {
                     if got == 0 {
+                        // Channel closed. During shutdown the producer loops have
+                        // dropped their senders (and we dropped ours), so this is
+                        // the expected end-of-stream: stop filling, fall through
+                        // to issue whatever partial batch we've accumulated, and
+                        // let the next loop iteration enter `drain_and_shutdown`
+                        // for the synchronous final commit + DLQ flush. Outside
+                        // shutdown a closed channel is unexpected and fatal.
+                        if shutdown.is_cancelled() {
+                            break;
+                        }
                         warn!("Coordinator recv failed, dying");
                         return Ok(());
                     }
@@ -179,7 +749,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 _ = sleep => {
                     if batch_start.elapsed() > Duration::from_secs(config.max_issue_period) {
                         warn!("Forcing small batch due to time limit");
-                        metrics::counter!(FORCED_SMALL_BATCH).increment(1);
+                        metrics_buf.increment(FORCED_SMALL_BATCH, 1);
                         break;
                     }
                 }
@@ -187,6 +757,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         batch_time.fin();
 
+        if batch.is_empty() {
+            // Nothing to issue — e.g. a shutdown-triggered channel close with no
+            // partial batch, or an idle forced-small-batch tick. Loop back so a
+            // pending shutdown enters the drain path on the next iteration rather
+            // than acquiring a permit and spawning an empty transaction.
+            continue;
+        }
+
         metrics::gauge!(CACHE_CONSUMED).set(cache.len() as f64);
 
         metrics::gauge!(TRANSACTION_LIMIT_SATURATION).set(
@@ -200,12 +778,36 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let permit = transaction_limit.clone().acquire_owned().await.unwrap();
         permit_acquire_time.fin();
 
+        // Split the carried offsets back out so we can commit exactly those
+        // high-water marks once the transaction durably lands.
+        let (updates, sources): (Vec<Update>, Vec<SourceOffset>) = batch.into_iter().unzip();
+
         let context = context.clone();
-        tokio::spawn(async move {
-            let _permit = permit;
-            let issue_time = common_metrics::timing_guard(UPDATE_ISSUE_TIME, &[]);
-            context.issue(batch).await.unwrap();
-            issue_time.fin();
-        });
+        let dlq = dlq.clone();
+        let offsets = offsets.clone();
+        let consumer = consumer.clone();
+        let batcher = batcher.clone();
+        let transaction_limit = transaction_limit.clone();
+        let commit_mode = config.commit_mode;
+        let max_issue_retries = config.max_issue_retries;
+        let topic = config.kafka.event_topic.clone();
+        let shutdown = shutdown.clone();
+        let unhealthy_shutdown = unhealthy_shutdown.clone();
+        tokio::spawn(issue_batch_with_retries(
+            context,
+            transaction_limit,
+            offsets,
+            consumer,
+            dlq,
+            batcher,
+            commit_mode,
+            max_issue_retries,
+            topic,
+            updates,
+            sources,
+            shutdown,
+            unhealthy_shutdown,
+            Some(permit),
+        ));
     }
 }
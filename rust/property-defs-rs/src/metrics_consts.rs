@@ -0,0 +1,34 @@
+// Metric names are centralised here so the registry keys stay consistent across
+// the producer loops, the coordinator, and the DLQ. Everything is prefixed with
+// `prop_defs_` so it groups cleanly in dashboards.
+
+pub const EMPTY_EVENTS: &str = "prop_defs_empty_events";
+pub const EVENT_PARSE_ERROR: &str = "prop_defs_event_parse_errors";
+
+pub const EVENTS_RECEIVED: &str = "prop_defs_events_received";
+pub const UPDATES_SEEN: &str = "prop_defs_updates_seen";
+pub const UPDATES_PER_EVENT: &str = "prop_defs_updates_per_event";
+pub const COMPACTED_UPDATES: &str = "prop_defs_compacted_updates";
+pub const UPDATES_FILTERED_BY_CACHE: &str = "prop_defs_filtered_by_cache";
+pub const WORKER_BLOCKED: &str = "prop_defs_worker_blocked";
+
+pub const CACHE_CONSUMED: &str = "prop_defs_cache_space";
+pub const RECV_DEQUEUED: &str = "prop_defs_recv_dequeued";
+pub const FORCED_SMALL_BATCH: &str = "prop_defs_forced_small_batch";
+
+pub const BATCH_ACQUIRE_TIME: &str = "prop_defs_batch_acquire_time_ms";
+pub const PERMIT_WAIT_TIME: &str = "prop_defs_permit_wait_time_ms";
+pub const UPDATE_ISSUE_TIME: &str = "prop_defs_update_issue_time_ms";
+pub const TRANSACTION_LIMIT_SATURATION: &str = "prop_defs_transaction_limit_saturation";
+
+// Adaptive batch sizing (chunk0-3).
+pub const ADAPTIVE_BATCH_TARGET: &str = "prop_defs_adaptive_batch_target";
+
+// Retry/backoff classification for issue() (chunk0-4).
+pub const ISSUE_RETRIES: &str = "prop_defs_issue_retries";
+pub const ISSUE_RETRIES_EXHAUSTED: &str = "prop_defs_issue_retries_exhausted";
+pub const ISSUE_PERMANENT_DROPS: &str = "prop_defs_issue_permanent_drops";
+
+// Dead-letter queue (chunk0-1).
+pub const DLQ_PRODUCED: &str = "prop_defs_dlq_produced";
+pub const DLQ_PRODUCE_FAILED: &str = "prop_defs_dlq_produce_failed";
@@ -0,0 +1,174 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rdkafka::{consumer::Consumer, consumer::StreamConsumer, TopicPartitionList};
+use serde::Serialize;
+use tracing::warn;
+
+/// Identifies where an `Update` originated so we can commit offsets only after
+/// the update has been durably written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct SourceOffset {
+    pub partition: i32,
+    pub offset: i64,
+}
+
+/// Tracks, per partition, which offsets are still in flight so we can advance
+/// the commit watermark without ever leaving a gap.
+///
+/// A Kafka message fans out into many `Update`s that may land in different
+/// coordinator batches, so an offset can be "tracked" more than once; we keep a
+/// pending count and only consider an offset complete when every update that
+/// carried it has been issued. The committable watermark for a partition is the
+/// highest offset for which no lower offset is still pending.
+#[derive(Default)]
+pub struct OffsetTracker {
+    topic: String,
+    partitions: HashMap<i32, PartitionState>,
+}
+
+#[derive(Default)]
+struct PartitionState {
+    pending: BTreeMap<i64, usize>,
+    max_seen: i64,
+    last_committed: i64,
+}
+
+impl PartitionState {
+    /// Highest offset safe to commit: one below the smallest still-pending
+    /// offset, or `max_seen` once nothing is pending.
+    fn watermark(&self) -> i64 {
+        match self.pending.keys().next() {
+            Some(smallest_pending) => smallest_pending - 1,
+            None => self.max_seen,
+        }
+    }
+}
+
+impl OffsetTracker {
+    pub fn new(topic: String) -> Self {
+        Self {
+            topic,
+            partitions: HashMap::new(),
+        }
+    }
+
+    /// Register an offset as entering the pipeline.
+    pub fn track(&mut self, source: SourceOffset) {
+        let state = self.partitions.entry(source.partition).or_default();
+        *state.pending.entry(source.offset).or_insert(0) += 1;
+        state.max_seen = state.max_seen.max(source.offset);
+    }
+
+    /// Mark one occurrence of an offset as durably written.
+    pub fn complete(&mut self, source: SourceOffset) {
+        let Some(state) = self.partitions.get_mut(&source.partition) else {
+            return;
+        };
+        if let Some(count) = state.pending.get_mut(&source.offset) {
+            *count -= 1;
+            if *count == 0 {
+                state.pending.remove(&source.offset);
+            }
+        }
+    }
+
+    /// Store the gap-free commit watermark for every partition that has
+    /// advanced since the last call. Only records the offsets locally with
+    /// `store_offsets`; in manual mode auto-commit is disabled, so the caller
+    /// drives the actual commit explicitly — asynchronously after each
+    /// successful batch and synchronously during the shutdown drain.
+    pub fn store(&mut self, consumer: &StreamConsumer) {
+        let mut tpl = TopicPartitionList::new();
+        for (partition, state) in self.partitions.iter_mut() {
+            let watermark = state.watermark();
+            if watermark <= state.last_committed {
+                continue;
+            }
+            state.last_committed = watermark;
+            // store_offset records the next offset to consume (last processed + 1).
+            tpl.add_partition_offset(
+                &self.topic,
+                *partition,
+                rdkafka::Offset::Offset(watermark + 1),
+            )
+            .expect("failed to add partition offset");
+        }
+        if tpl.count() > 0 {
+            if let Err(e) = consumer.store_offsets(&tpl) {
+                warn!("Failed to store offsets: {:?}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn src(partition: i32, offset: i64) -> SourceOffset {
+        SourceOffset { partition, offset }
+    }
+
+    fn watermark(tracker: &OffsetTracker, partition: i32) -> i64 {
+        tracker.partitions.get(&partition).unwrap().watermark()
+    }
+
+    #[test]
+    fn watermark_advances_to_max_when_all_complete() {
+        let mut tracker = OffsetTracker::new("topic".into());
+        for offset in [5, 6, 7] {
+            tracker.track(src(0, offset));
+        }
+        for offset in [5, 6, 7] {
+            tracker.complete(src(0, offset));
+        }
+        assert_eq!(watermark(&tracker, 0), 7);
+    }
+
+    #[test]
+    fn watermark_pins_below_smallest_pending() {
+        let mut tracker = OffsetTracker::new("topic".into());
+        for offset in [5, 6, 7] {
+            tracker.track(src(0, offset));
+        }
+        // Out-of-order completion: higher offsets land first, but the gap at 5
+        // must keep the watermark pinned below it.
+        tracker.complete(src(0, 6));
+        tracker.complete(src(0, 7));
+        assert_eq!(watermark(&tracker, 0), 4);
+        tracker.complete(src(0, 5));
+        assert_eq!(watermark(&tracker, 0), 7);
+    }
+
+    #[test]
+    fn compaction_fan_out_needs_all_completions() {
+        let mut tracker = OffsetTracker::new("topic".into());
+        // One Kafka message fanned into three updates all carrying offset 9.
+        for _ in 0..3 {
+            tracker.track(src(0, 9));
+        }
+        tracker.complete(src(0, 9));
+        tracker.complete(src(0, 9));
+        // One occurrence still pending — the offset is not yet fully written.
+        assert_eq!(watermark(&tracker, 0), 8);
+        tracker.complete(src(0, 9));
+        assert_eq!(watermark(&tracker, 0), 9);
+    }
+
+    #[test]
+    fn partitions_advance_independently() {
+        let mut tracker = OffsetTracker::new("topic".into());
+        tracker.track(src(0, 1));
+        tracker.track(src(1, 100));
+        tracker.complete(src(1, 100));
+        assert_eq!(watermark(&tracker, 0), 0);
+        assert_eq!(watermark(&tracker, 1), 100);
+    }
+
+    #[test]
+    fn completing_unknown_offset_is_a_noop() {
+        let mut tracker = OffsetTracker::new("topic".into());
+        tracker.complete(src(0, 42));
+        assert!(tracker.partitions.get(&0).is_none());
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Whether a failed `issue()` is worth retrying. Transient errors (serialization
+/// failures, deadlocks, connection resets, pool timeouts) usually clear on their
+/// own; permanent errors (malformed updates, constraint violations) will fail
+/// identically forever and are routed out of the batch instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorClass {
+    Transient,
+    Permanent,
+}
+
+/// Classify a `sqlx` error into transient vs permanent.
+pub fn classify(err: &sqlx::Error) -> ErrorClass {
+    match err {
+        sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => {
+            ErrorClass::Transient
+        }
+        sqlx::Error::Database(db) => match db.code().as_deref() {
+            // 40001 serialization_failure, 40P01 deadlock_detected,
+            // 57014 query_canceled, 08xxx connection exceptions.
+            Some("40001") | Some("40P01") | Some("57014") => ErrorClass::Transient,
+            Some(code) if code.starts_with("08") => ErrorClass::Transient,
+            _ => ErrorClass::Permanent,
+        },
+        _ => ErrorClass::Permanent,
+    }
+}
+
+/// Exponential backoff with full jitter, capped at `max`. Attempt is 0-based.
+pub fn backoff_with_jitter(attempt: u32, base: Duration, max: Duration) -> Duration {
+    let exp = base
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(max);
+    let jitter = rand::thread_rng().gen_range(0..=exp.as_millis() as u64);
+    Duration::from_millis(jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+    use std::error::Error as StdError;
+    use std::fmt;
+
+    /// Minimal `DatabaseError` so we can drive `classify`'s SQLSTATE branch
+    /// without a live connection.
+    #[derive(Debug)]
+    struct TestDbError {
+        code: Option<String>,
+    }
+
+    impl fmt::Display for TestDbError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "test db error")
+        }
+    }
+
+    impl StdError for TestDbError {}
+
+    impl sqlx::error::DatabaseError for TestDbError {
+        fn message(&self) -> &str {
+            "test db error"
+        }
+        fn code(&self) -> Option<Cow<'_, str>> {
+            self.code.as_deref().map(Cow::Borrowed)
+        }
+        fn as_error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+            self
+        }
+        fn as_error_mut(&mut self) -> &mut (dyn StdError + Send + Sync + 'static) {
+            self
+        }
+        fn into_error(self: Box<Self>) -> Box<dyn StdError + Send + Sync + 'static> {
+            self
+        }
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+
+    fn db_err(code: &str) -> sqlx::Error {
+        sqlx::Error::Database(Box::new(TestDbError {
+            code: Some(code.to_string()),
+        }))
+    }
+
+    #[test]
+    fn pool_and_io_errors_are_transient() {
+        assert_eq!(classify(&sqlx::Error::PoolTimedOut), ErrorClass::Transient);
+        assert_eq!(classify(&sqlx::Error::PoolClosed), ErrorClass::Transient);
+        let io = sqlx::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "reset",
+        ));
+        assert_eq!(classify(&io), ErrorClass::Transient);
+    }
+
+    #[test]
+    fn known_sqlstates_are_transient() {
+        for code in ["40001", "40P01", "57014", "08006", "08000"] {
+            assert_eq!(classify(&db_err(code)), ErrorClass::Transient, "{code}");
+        }
+    }
+
+    #[test]
+    fn other_errors_are_permanent() {
+        assert_eq!(classify(&sqlx::Error::RowNotFound), ErrorClass::Permanent);
+        // A unique-constraint violation is deterministic — never worth retrying.
+        assert_eq!(classify(&db_err("23505")), ErrorClass::Permanent);
+    }
+
+    #[test]
+    fn backoff_stays_within_max_and_base() {
+        let base = Duration::from_millis(50);
+        let max = Duration::from_secs(5);
+        for attempt in 0..12 {
+            assert!(
+                backoff_with_jitter(attempt, base, max) <= max,
+                "attempt {attempt} exceeded max"
+            );
+        }
+        // Full jitter at attempt 0 ranges over [0, base].
+        for _ in 0..100 {
+            assert!(backoff_with_jitter(0, base, max) <= base);
+        }
+    }
+}